@@ -12,7 +12,7 @@ use std::path::PathBuf;
 
 use clap::ValueHint;
 use internet2::addr::ServiceAddr;
-use rgb::Contract;
+use rgb::{Contract, ContractId};
 use rgb_rpc::{RGB_NODE_DATA_DIR, RGB_NODE_RPC_ENDPOINT};
 
 /// Command-line tool for working with RGB node
@@ -65,4 +65,34 @@ pub enum Command {
     /// Add new contract to the node
     #[display("register(...)")]
     Register { contract: Contract },
+
+    /// Import an RGB interface definition (e.g. RGB20) from a file
+    #[display("import_interface(...)")]
+    ImportInterface {
+        /// Path to the file containing the strict-encoded interface
+        #[clap(value_hint = ValueHint::FilePath)]
+        path: PathBuf,
+    },
+
+    /// Bind a schema to a previously-imported interface, so contracts using
+    /// that schema can be read through the interface's field names
+    #[display("import_implementation(...)")]
+    ImportImplementation {
+        /// Path to the file containing the strict-encoded implementation
+        #[clap(value_hint = ValueHint::FilePath)]
+        path: PathBuf,
+    },
+
+    /// Print a contract's global and owned state using an interface's
+    /// human-readable field and assignment names
+    #[display("contract_state(...)")]
+    ContractState {
+        /// Contract to query
+        contract: ContractId,
+
+        /// Name of the interface to render the state through, as passed to
+        /// `import_interface` (resolved to the `IfaceId` the daemon indexes
+        /// by via `Runtime::iface_id_by_name`)
+        iface: String,
+    },
 }
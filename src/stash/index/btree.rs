@@ -11,19 +11,302 @@
 // along with this software.
 // If not, see <https://opensource.org/licenses/MIT>.
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use bitcoin::{OutPoint, Txid};
+use rgb::{AnchorId, ContractId, NodeId};
+use strict_encoding::{StrictDecode, StrictEncode};
 
 use super::Index;
 
+#[derive(Clone, PartialEq, Eq, Debug, Display, Error, From)]
+#[display(Debug)]
+pub enum BTreeIndexError {
+    #[from(io::Error)]
+    #[from(strict_encoding::Error)]
+    Io,
+
+    NotFound,
+}
+
+/// Disk-backed reverse-lookup index over the stash: keeps every id
+/// relationship `consign`/`forget`/`prune` need so they don't have to walk
+/// storage blind. Storage still owns the node/anchor contents; this only
+/// tracks which ids exist and how they relate.
+#[derive(Clone, PartialEq, Eq, Debug, Default, StrictEncode, StrictDecode)]
+struct BtreeIndexData {
+    /// `NodeId -> AnchorId` of the anchor committing a transition.
+    transition_anchors: BTreeMap<NodeId, AnchorId>,
+    /// `NodeId -> ContractId` owning a transition or extension.
+    node_contracts: BTreeMap<NodeId, ContractId>,
+    /// `ContractId -> {NodeId}` of every node known to belong to a
+    /// contract (the reverse of `node_contracts`).
+    contract_nodes: BTreeMap<ContractId, BTreeSet<NodeId>>,
+    /// `OutPoint -> NodeId` of the transition/extension whose assignment
+    /// is controlled by a known (revealed) seal at that outpoint.
+    outpoint_nodes: BTreeMap<OutPoint, NodeId>,
+    /// `AnchorId -> Txid` of the witness transaction committing an anchor.
+    anchor_witnesses: BTreeMap<AnchorId, Txid>,
+    /// `Txid -> {AnchorId}`, the reverse of `anchor_witnesses`, so a
+    /// new-block/reorg notification can invalidate just the affected
+    /// anchors instead of the whole resolver cache.
+    witness_anchors: BTreeMap<Txid, BTreeSet<AnchorId>>,
+}
+
 #[derive(Display, Debug)]
 #[display_from(Debug)]
 pub struct BtreeIndex {
-    index: BTreeMap<Vec<u8>, Vec<u8>>,
+    /// Where the index is persisted; `None` keeps it purely in-memory
+    /// (used by tests and other throwaway runtimes).
+    path: Option<PathBuf>,
+    data: BtreeIndexData,
 }
 
 impl BtreeIndex {
     pub fn new() -> Self {
-        Self { index: bmap! {} }
+        Self {
+            path: None,
+            data: BtreeIndexData::default(),
+        }
+    }
+
+    /// Opens a persistent index at `path`, loading its current contents if
+    /// the file exists or starting empty otherwise.
+    pub fn open(path: PathBuf) -> Result<Self, BTreeIndexError> {
+        let data = if path.exists() {
+            let file = fs::File::open(&path)?;
+            BtreeIndexData::strict_decode(file)?
+        } else {
+            BtreeIndexData::default()
+        };
+        Ok(Self {
+            path: Some(path),
+            data,
+        })
+    }
+
+    /// Atomically persists the index: writes to a sibling temp file, then
+    /// renames it over `path`, so a crash mid-write can't leave a
+    /// half-written index behind.
+    fn store(&self) -> Result<(), BTreeIndexError> {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        let tmp = path.with_extension("tmp");
+        let file = fs::File::create(&tmp)?;
+        self.data.strict_encode(file)?;
+        fs::rename(&tmp, path)?;
+        Ok(())
+    }
+
+    /// Persists whatever in-memory changes have accumulated since the last
+    /// write. `index_transition`/`index_node`/`index_outpoint`/`add_witness`
+    /// don't persist on every call (a single `merge` can call several of
+    /// them per transition); callers batching a group of those must flush
+    /// once when the group is done.
+    pub fn flush(&self) -> Result<(), BTreeIndexError> {
+        self.store()
+    }
+
+    /// Records that `anchor_id` commits `node_id`, and that `node_id`
+    /// belongs to `contract_id`. Called from `merge` so the index stays
+    /// consistent with every storage write. Does not persist by itself;
+    /// call [`Self::flush`] once the batch of index updates is complete.
+    pub fn index_transition(
+        &mut self,
+        contract_id: ContractId,
+        node_id: NodeId,
+        anchor_id: AnchorId,
+    ) -> Result<(), BTreeIndexError> {
+        self.data.transition_anchors.insert(node_id, anchor_id);
+        self.index_node(contract_id, node_id)
+    }
+
+    /// Records that `contract_id` exists, independent of any node. Called
+    /// on contract registration so a contract with no transitions yet is
+    /// still visible to `contract_ids()`/`genesis_iter()` (both of which
+    /// otherwise only learn of a contract once `index_node` runs from the
+    /// first `merge`).
+    pub fn index_genesis(
+        &mut self,
+        contract_id: ContractId,
+    ) -> Result<(), BTreeIndexError> {
+        self.data.contract_nodes.entry(contract_id).or_insert_with(BTreeSet::new);
+        self.store()
+    }
+
+    /// Records that `node_id` belongs to `contract_id`, without an anchor
+    /// (used for extensions, which aren't anchored to a witness tx). Does
+    /// not persist by itself; call [`Self::flush`] once the batch of index
+    /// updates is complete.
+    pub fn index_node(
+        &mut self,
+        contract_id: ContractId,
+        node_id: NodeId,
+    ) -> Result<(), BTreeIndexError> {
+        self.data.node_contracts.insert(node_id, contract_id);
+        self.data
+            .contract_nodes
+            .entry(contract_id)
+            .or_insert_with(BTreeSet::new)
+            .insert(node_id);
+        Ok(())
+    }
+
+    /// Records that the seal at `outpoint` is controlled by `node_id`. Does
+    /// not persist by itself; call [`Self::flush`] once the batch of index
+    /// updates is complete.
+    pub fn index_outpoint(
+        &mut self,
+        outpoint: OutPoint,
+        node_id: NodeId,
+    ) -> Result<(), BTreeIndexError> {
+        self.data.outpoint_nodes.insert(outpoint, node_id);
+        Ok(())
+    }
+
+    /// Records the witness transaction committing `anchor_id`. Does not
+    /// persist by itself; call [`Self::flush`] once the batch of index
+    /// updates is complete.
+    pub fn add_witness(
+        &mut self,
+        anchor_id: AnchorId,
+        txid: Txid,
+    ) -> Result<(), BTreeIndexError> {
+        self.data.anchor_witnesses.insert(anchor_id, txid);
+        self.data
+            .witness_anchors
+            .entry(txid)
+            .or_insert_with(BTreeSet::new)
+            .insert(anchor_id);
+        Ok(())
+    }
+
+    /// Witness transaction committing `anchor_id`, if known.
+    pub fn witness_txid(&self, anchor_id: AnchorId) -> Option<Txid> {
+        self.data.anchor_witnesses.get(&anchor_id).copied()
+    }
+
+    /// Anchors committed by `txid`, for targeted cache invalidation on
+    /// reorg notifications.
+    pub fn anchors_by_witness(&self, txid: Txid) -> BTreeSet<AnchorId> {
+        self.data
+            .witness_anchors
+            .get(&txid)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Contract owning `node_id`, if the node is known to the index.
+    pub fn contract_id_by_node_id(
+        &self,
+        node_id: NodeId,
+    ) -> Result<ContractId, BTreeIndexError> {
+        self.data
+            .node_contracts
+            .get(&node_id)
+            .copied()
+            .ok_or(BTreeIndexError::NotFound)
+    }
+
+    /// Every node id known to belong to `contract_id`.
+    pub fn node_ids_by_contract_id(&self, contract_id: ContractId) -> BTreeSet<NodeId> {
+        self.data
+            .contract_nodes
+            .get(&contract_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Node whose assignment controls the seal at `outpoint`, if known.
+    pub fn node_id_by_outpoint(
+        &self,
+        outpoint: OutPoint,
+    ) -> Result<NodeId, BTreeIndexError> {
+        self.data
+            .outpoint_nodes
+            .get(&outpoint)
+            .copied()
+            .ok_or(BTreeIndexError::NotFound)
+    }
+
+    /// Every contract id known to the index.
+    pub fn contract_ids(&self) -> impl Iterator<Item = ContractId> + '_ {
+        self.data.contract_nodes.keys().copied()
+    }
+
+    /// Every (non-genesis) node id known to the index.
+    pub fn node_ids(&self) -> impl Iterator<Item = NodeId> + '_ {
+        self.data.node_contracts.keys().copied()
+    }
+
+    /// Every node id known to be committed by `anchor_id` (an MPC anchor
+    /// commonly commits more than one transition in a single witness
+    /// transaction, so this can return more than one node).
+    pub fn node_ids_by_anchor_id(&self, anchor_id: AnchorId) -> BTreeSet<NodeId> {
+        self.data
+            .transition_anchors
+            .iter()
+            .filter_map(|(node_id, id)| (*id == anchor_id).then(|| *node_id))
+            .collect()
+    }
+
+    /// Every anchor id known to the index.
+    pub fn anchor_ids(&self) -> impl Iterator<Item = AnchorId> + '_ {
+        self.data
+            .transition_anchors
+            .values()
+            .copied()
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+    }
+
+    /// Drops every entry that references `node_id` (used by `forget`/
+    /// `prune` once the node itself has been removed from storage). If the
+    /// node's anchor isn't committing any other known node any more, its
+    /// witness-tracking entries are dropped too, so `anchor_witnesses`/
+    /// `witness_anchors` don't grow unboundedly across GC cycles.
+    pub fn remove_node(&mut self, node_id: NodeId) -> Result<(), BTreeIndexError> {
+        if let Some(anchor_id) = self.data.transition_anchors.remove(&node_id) {
+            let still_committed = self
+                .data
+                .transition_anchors
+                .values()
+                .any(|id| *id == anchor_id);
+            if !still_committed {
+                if let Some(txid) = self.data.anchor_witnesses.remove(&anchor_id) {
+                    if let Some(anchors) = self.data.witness_anchors.get_mut(&txid) {
+                        anchors.remove(&anchor_id);
+                        if anchors.is_empty() {
+                            self.data.witness_anchors.remove(&txid);
+                        }
+                    }
+                }
+            }
+        }
+        if let Some(contract_id) = self.data.node_contracts.remove(&node_id) {
+            if let Some(nodes) = self.data.contract_nodes.get_mut(&contract_id) {
+                nodes.remove(&node_id);
+            }
+        }
+        self.data.outpoint_nodes.retain(|_, id| *id != node_id);
+        self.store()
+    }
+
+    /// Anchor committing the transition `node_id`.
+    pub fn anchor_id_by_transition_id(
+        &self,
+        node_id: NodeId,
+    ) -> Result<AnchorId, BTreeIndexError> {
+        self.data
+            .transition_anchors
+            .get(&node_id)
+            .copied()
+            .ok_or(BTreeIndexError::NotFound)
     }
 }
 
@@ -0,0 +1,112 @@
+// RGB node providing smart contracts functionality for Bitcoin & Lightning.
+//
+// Written in 2022 by
+//     Dr. Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// Copyright (C) 2022 by LNP/BP Standards Association, Switzerland.
+//
+// You should have received a copy of the MIT License along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+//! Tracking of the mining status of witness transactions committing RGB
+//! anchors, used by [`super::stash`] to decide which state is safe to hand
+//! out in a consignment.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use bitcoin::Txid;
+
+/// Mining status of a witness transaction, as seen by a [`WitnessResolver`]
+/// backend.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Display)]
+pub enum MiningStatus {
+    /// The backend has no knowledge of this transaction: it was never
+    /// broadcast, dropped from the mempool, or reorged out without being
+    /// re-mined.
+    #[display("archived")]
+    Archived,
+
+    /// The transaction is known to the backend but not yet included in a
+    /// block.
+    #[display("tentative")]
+    Tentative,
+
+    /// The transaction is included in a block at the given height.
+    #[display("mined({0})")]
+    Mined(u32),
+}
+
+/// A pluggable backend able to answer mining-status queries for witness
+/// transactions, abstracting over a Bitcoin Core or Electrum connection.
+pub trait WitnessResolver {
+    type Error: std::error::Error;
+
+    /// Returns the current mining status of `txid`.
+    fn mining_status(&self, txid: &Txid) -> Result<MiningStatus, Self::Error>;
+}
+
+/// Wraps a [`WitnessResolver`] backend with a per-txid cache, so that
+/// repeated `consign` calls don't re-query the backend for witnesses whose
+/// status can't have changed since the last new-block notification.
+///
+/// The cache is cleared wholesale on [`CachingResolver::new_block`]; callers
+/// that know exactly which anchors a reorged-out block affected can instead
+/// use [`CachingResolver::invalidate`] to drop a single entry.
+pub struct CachingResolver<R: WitnessResolver> {
+    inner: R,
+    chain_tip: RefCell<Option<u32>>,
+    cache: RefCell<HashMap<Txid, MiningStatus>>,
+}
+
+impl<R: WitnessResolver> CachingResolver<R> {
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            chain_tip: RefCell::new(None),
+            cache: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Height of the chain tip as of the last [`Self::new_block`]
+    /// notification, if any was received yet.
+    pub fn chain_tip(&self) -> Option<u32> {
+        *self.chain_tip.borrow()
+    }
+
+    /// Must be called on every new-block (and reorg) notification: a
+    /// previously `Tentative` or `Archived` witness may now be mined, and a
+    /// previously `Mined` one may have been reorged out, so the whole cache
+    /// is invalidated and the chain tip updated.
+    pub fn new_block(&self, height: u32) {
+        *self.chain_tip.borrow_mut() = Some(height);
+        self.cache.borrow_mut().clear();
+    }
+
+    /// Drops the cached status for a single txid, forcing the next lookup
+    /// to hit the backend again.
+    pub fn invalidate(&self, txid: &Txid) {
+        self.cache.borrow_mut().remove(txid);
+    }
+
+    pub fn mining_status(&self, txid: &Txid) -> Result<MiningStatus, R::Error> {
+        if let Some(status) = self.cache.borrow().get(txid) {
+            return Ok(*status);
+        }
+        let status = self.inner.mining_status(txid)?;
+        self.cache.borrow_mut().insert(*txid, status);
+        Ok(status)
+    }
+
+    /// Number of confirmations `txid` has as of the last known chain tip,
+    /// or `None` if it isn't mined or the chain tip hasn't been learned yet.
+    pub fn confirmations(&self, txid: &Txid) -> Result<Option<u32>, R::Error> {
+        let status = self.mining_status(txid)?;
+        Ok(match (status, self.chain_tip()) {
+            (MiningStatus::Mined(height), Some(tip)) => {
+                tip.checked_sub(height).map(|depth| depth + 1)
+            }
+            _ => None,
+        })
+    }
+}
@@ -14,12 +14,19 @@
 use std::collections::{BTreeSet, VecDeque};
 
 use bitcoin::hashes::Hash;
+use bitcoin::OutPoint;
 use lnpbp::client_side_validation::Conceal;
 use lnpbp::seals::{OutpointHash, OutpointReveal};
+use rgb::reveal::MergeReveal;
 use rgb::{
-    Anchor, Assignments, AutoConceal, Consignment, ContractId, Disclosure,
-    Extension, Genesis, Node, NodeId, SchemaId, SealEndpoint, Stash, Transition,
+    Anchor, AnchorId, Assignments, AutoConceal, Consignment, ContractId,
+    Disclosure, Extension, Genesis, Node, NodeId, SchemaId, SealEndpoint, Stash,
+    Transition,
 };
+use strict_encoding::StrictEncode;
+
+use super::iface::{ContractState, Iface, IfaceId, IfaceImpl};
+use super::resolver::{CachingResolver, WitnessResolver};
 
 use super::index::Index;
 use super::storage::Store;
@@ -37,24 +44,83 @@ pub enum Error {
     AnchorParameterIsRequired,
 
     GenesisNode,
+
+    /// the stash already holds a copy of this node or anchor that can't be
+    /// reconciled with the one being merged in into a single revealed state
+    /// (e.g. their metadata or parent rights differ)
+    MergeConflict,
+
+    UnknownIface,
+
+    UnknownIfaceImpl,
+
+    /// the node being consigned is itself a transition whose witness
+    /// transaction isn't mined `min_confirmations` deep, so no consignment
+    /// can be produced for it at all (it would otherwise come back
+    /// without the very state that was asked for)
+    TipNotSufficientlyConfirmed,
 }
 
-pub struct DumbIter<T>(std::marker::PhantomData<T>);
-impl<T> Iterator for DumbIter<T> {
-    type Item = T;
+/// Outpoint controlled by `seal`, if it's revealed rather than concealed.
+fn revealed_outpoint(seal: &SealEndpoint) -> Option<OutPoint> {
+    match seal {
+        SealEndpoint::ConcealedUtxo(_) => None,
+        SealEndpoint::Revealed(reveal) => Some(OutPoint::from(reveal.clone())),
+    }
+}
 
-    fn next(&mut self) -> Option<Self::Item> {
-        unimplemented!()
+/// Outcome of a garbage-collection pass: how many nodes were dropped and
+/// the combined strict-encoded size of everything actually removed from
+/// storage (nodes and any anchors reclaimed alongside them).
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct Reclaimed {
+    pub nodes: usize,
+    pub bytes: usize,
+}
+
+/// Strict-encoded size of `data`, used to account for bytes reclaimed by
+/// `forget`/`prune` once a node or anchor is dropped from storage.
+fn encoded_len(data: &impl StrictEncode) -> usize {
+    let mut buf = vec![];
+    data.strict_encode(&mut buf).unwrap_or(0)
+}
+
+/// Folds a later tip's assignments for the same owned right into `acc`,
+/// concatenating rather than overwriting: more than one still-live tip can
+/// hold the same right (e.g. an asset split across several UTXOs), and all
+/// of them are part of the contract's current state.
+fn merge_assignments(
+    acc: Option<Assignments>,
+    next: Assignments,
+) -> Assignments {
+    match (acc, next) {
+        (None, next) => next,
+        (Some(Assignments::Declarative(mut a)), Assignments::Declarative(b)) => {
+            a.extend(b);
+            Assignments::Declarative(a)
+        }
+        (
+            Some(Assignments::DiscreteFiniteField(mut a)),
+            Assignments::DiscreteFiniteField(b),
+        ) => {
+            a.extend(b);
+            Assignments::DiscreteFiniteField(a)
+        }
+        (Some(Assignments::CustomData(mut a)), Assignments::CustomData(b)) => {
+            a.extend(b);
+            Assignments::CustomData(a)
+        }
+        (Some(acc), _) => acc,
     }
 }
 
 impl Stash for Runtime {
     type Error = Error;
-    type GenesisIterator = DumbIter<Genesis>;
-    type AnchorIterator = DumbIter<Anchor>;
-    type TransitionIterator = DumbIter<Transition>;
-    type ExtensionIterator = DumbIter<Extension>;
-    type NidIterator = DumbIter<NodeId>;
+    type GenesisIterator = std::vec::IntoIter<Genesis>;
+    type AnchorIterator = std::vec::IntoIter<Anchor>;
+    type TransitionIterator = std::vec::IntoIter<Transition>;
+    type ExtensionIterator = std::vec::IntoIter<Extension>;
+    type NidIterator = std::vec::IntoIter<NodeId>;
 
     fn get_schema(
         &self,
@@ -92,19 +158,39 @@ impl Stash for Runtime {
     }
 
     fn genesis_iter(&self) -> Self::GenesisIterator {
-        unimplemented!()
+        self.indexer
+            .contract_ids()
+            .filter_map(|contract_id| self.storage.genesis(&contract_id).ok())
+            .collect::<Vec<_>>()
+            .into_iter()
     }
 
     fn anchor_iter(&self) -> Self::AnchorIterator {
-        unimplemented!()
+        self.indexer
+            .anchor_ids()
+            .filter_map(|anchor_id| self.storage.anchor(&anchor_id).ok())
+            .collect::<Vec<_>>()
+            .into_iter()
     }
 
     fn transition_iter(&self) -> Self::TransitionIterator {
-        unimplemented!()
+        self.indexer
+            .node_ids()
+            .filter_map(|node_id| self.storage.transition(&node_id).ok())
+            .collect::<Vec<_>>()
+            .into_iter()
     }
 
     fn extension_iter(&self) -> Self::ExtensionIterator {
-        unimplemented!()
+        self.indexer
+            .node_ids()
+            .filter_map(|node_id| self.storage.extension(&node_id).ok())
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+
+    fn nid_iter(&self) -> Self::NidIterator {
+        self.indexer.node_ids().collect::<Vec<_>>().into_iter()
     }
 
     fn consign(
@@ -238,9 +324,47 @@ impl Stash for Runtime {
                 .owned_rights_mut()
                 .into_iter()
                 .for_each(reveal_known_seals);
-            // Store the transition and the anchor data in the stash
+
+            // [MERGE-REVEAL]:
+            // If the stash already holds a (possibly less-revealed) copy of
+            // this node, merge the two together rather than overwriting:
+            // a revealed assignment always wins over a concealed one, and
+            // anything that can't be reconciled is a hard conflict.
+            let transition = match self.storage.transition(&transition.node_id())
+            {
+                Ok(known) => known
+                    .merge_reveal(transition)
+                    .map_err(|_| Error::MergeConflict)?,
+                Err(_) => transition,
+            };
+            let anchor = match self.storage.anchor(&anchor.anchor_id()) {
+                Ok(known) => known
+                    .merge_reveal(anchor.clone())
+                    .map_err(|_| Error::MergeConflict)?,
+                Err(_) => anchor.clone(),
+            };
+
+            // Store the transition and the anchor data in the stash, and
+            // update the in-memory index next to each write. The index is
+            // persisted only by the flush after both loops: if an error
+            // returns early, the storage writes already made stay in place
+            // while these index changes are not persisted.
             self.storage.add_anchor(&anchor)?;
             self.storage.add_transition(&transition)?;
+            self.indexer.add_witness(anchor.anchor_id(), anchor.txid)?;
+            self.indexer.index_transition(
+                consignment.genesis.contract_id(),
+                transition.node_id(),
+                anchor.anchor_id(),
+            )?;
+            for (endpoint_node, seal) in consignment.endpoints.iter() {
+                if *endpoint_node != transition.node_id() {
+                    continue;
+                }
+                if let Some(outpoint) = revealed_outpoint(seal) {
+                    self.indexer.index_outpoint(outpoint, transition.node_id())?;
+                }
+            }
         }
 
         for extension in consignment.state_extensions.iter() {
@@ -249,24 +373,469 @@ impl Stash for Runtime {
                 .owned_rights_mut()
                 .into_iter()
                 .for_each(reveal_known_seals);
+
+            let extension = match self.storage.extension(&extension.node_id()) {
+                Ok(known) => known
+                    .merge_reveal(extension)
+                    .map_err(|_| Error::MergeConflict)?,
+                Err(_) => extension,
+            };
             self.storage.add_extension(&extension)?;
+            self.indexer.index_node(
+                consignment.genesis.contract_id(),
+                extension.node_id(),
+            )?;
+            for (endpoint_node, seal) in consignment.endpoints.iter() {
+                if *endpoint_node != extension.node_id() {
+                    continue;
+                }
+                if let Some(outpoint) = revealed_outpoint(seal) {
+                    self.indexer.index_outpoint(outpoint, extension.node_id())?;
+                }
+            }
         }
 
+        // `index_transition`/`index_node`/`index_outpoint`/`add_witness`
+        // above don't persist individually, so a consignment with many
+        // ancestors costs one index rewrite total instead of one per field
+        // update.
+        self.indexer.flush()?;
+
         Ok(())
     }
 
     fn forget(
         &mut self,
-        _consignment: Consignment,
+        consignment: Consignment,
     ) -> Result<usize, Self::Error> {
-        unimplemented!()
+        Ok(self.forget_reporting(consignment)?.nodes)
     }
 
+    /// The trait-level contract for `prune` has no way to receive a
+    /// resolver or `min_depth`, so it can't tell whether a stale node's
+    /// witness is actually beyond reorg risk. This is therefore a no-op
+    /// that removes nothing and returns 0: real pruning must go through
+    /// [`Runtime::prune_confirmed`], which does have the guard.
     fn prune(&mut self) -> Result<usize, Self::Error> {
-        unimplemented!()
+        Ok(0)
     }
 
     fn disclose(&self) -> Result<Disclosure, Self::Error> {
         unimplemented!()
     }
 }
+
+impl Runtime {
+    /// Node ids that `forget`/`prune` must never delete: every genesis, and
+    /// every ancestor of a node that is currently a tip (i.e. not itself
+    /// referenced as a parent by anything else in the stash).
+    fn retained_node_ids(&self) -> Result<BTreeSet<NodeId>, Error> {
+        let mut referenced = BTreeSet::new();
+        for transition in self.transition_iter() {
+            referenced.extend(
+                transition.parent_owned_rights().into_iter().map(|(id, _)| id),
+            );
+            referenced.extend(
+                transition.parent_public_rights().into_iter().map(|(id, _)| id),
+            );
+        }
+        for extension in self.extension_iter() {
+            referenced.extend(
+                extension.parent_owned_rights().into_iter().map(|(id, _)| id),
+            );
+            referenced.extend(
+                extension.parent_public_rights().into_iter().map(|(id, _)| id),
+            );
+        }
+
+        let mut retained = BTreeSet::new();
+        let mut queue = VecDeque::new();
+        queue.extend(self.genesis_iter().map(|genesis| genesis.node_id()));
+        queue.extend(
+            self.nid_iter().filter(|node_id| !referenced.contains(node_id)),
+        );
+
+        while let Some(node_id) = queue.pop_front() {
+            if !retained.insert(node_id) {
+                continue;
+            }
+            if let Ok(transition) = self.storage.transition(&node_id) {
+                queue.extend(
+                    transition
+                        .parent_owned_rights()
+                        .into_iter()
+                        .map(|(id, _)| id),
+                );
+                queue.extend(
+                    transition
+                        .parent_public_rights()
+                        .into_iter()
+                        .map(|(id, _)| id),
+                );
+            } else if let Ok(extension) = self.storage.extension(&node_id) {
+                queue.extend(
+                    extension
+                        .parent_owned_rights()
+                        .into_iter()
+                        .map(|(id, _)| id),
+                );
+                queue.extend(
+                    extension
+                        .parent_public_rights()
+                        .into_iter()
+                        .map(|(id, _)| id),
+                );
+            }
+        }
+
+        Ok(retained)
+    }
+
+    /// Whether `anchor_id` still commits a node other than `excluding` that
+    /// is itself retained. MPC anchors commonly commit more than one
+    /// transition in a single witness transaction, so an anchor must not be
+    /// dropped just because the one transition currently being garbage
+    /// collected no longer needs it.
+    fn anchor_still_needed(
+        &self,
+        anchor_id: AnchorId,
+        excluding: NodeId,
+        retained: &BTreeSet<NodeId>,
+    ) -> bool {
+        self.indexer
+            .node_ids_by_anchor_id(anchor_id)
+            .into_iter()
+            .any(|node_id| node_id != excluding && retained.contains(&node_id))
+    }
+
+    /// Implements [`Stash::forget`], additionally reporting the number of
+    /// nodes dropped and the strict-encoded bytes actually reclaimed from
+    /// storage (an anchor shared with a still-retained transition is left
+    /// alone, so its bytes aren't counted).
+    pub fn forget_reporting(
+        &mut self,
+        consignment: Consignment,
+    ) -> Result<Reclaimed, Error> {
+        let retained = self.retained_node_ids()?;
+        let mut reclaimed = Reclaimed::default();
+
+        for (anchor, transition) in consignment.state_transitions.iter() {
+            let node_id = transition.node_id();
+            if retained.contains(&node_id) {
+                continue;
+            }
+            let anchor_id = anchor.anchor_id();
+            let stored = self.storage.transition(&node_id).ok();
+            if self.storage.remove_transition(&node_id).is_ok() {
+                reclaimed.nodes += 1;
+                reclaimed.bytes += stored.as_ref().map_or(0, |t| encoded_len(t));
+            }
+            if !self.anchor_still_needed(anchor_id, node_id, &retained) {
+                let stored = self.storage.anchor(&anchor_id).ok();
+                if self.storage.remove_anchor(&anchor_id).is_ok() {
+                    reclaimed.bytes +=
+                        stored.as_ref().map_or(0, |a| encoded_len(a));
+                }
+            }
+            self.indexer.remove_node(node_id)?;
+        }
+
+        for extension in consignment.state_extensions.iter() {
+            let node_id = extension.node_id();
+            if retained.contains(&node_id) {
+                continue;
+            }
+            let stored = self.storage.extension(&node_id).ok();
+            if self.storage.remove_extension(&node_id).is_ok() {
+                reclaimed.nodes += 1;
+                reclaimed.bytes += stored.as_ref().map_or(0, |e| encoded_len(e));
+            }
+            self.indexer.remove_node(node_id)?;
+        }
+
+        Ok(reclaimed)
+    }
+
+    /// Reorg-safe variant of [`Stash::prune`]: like the base method, never
+    /// deletes anything reachable from a current tip, but additionally
+    /// leaves a stale transition alone unless its anchor's witness
+    /// transaction is mined at least `min_depth` deep, so a just-reorged
+    /// block can't take an irreplaceable node with it. An anchor shared
+    /// with a still-retained transition is likewise left alone. Reports the
+    /// number of nodes dropped and the strict-encoded bytes reclaimed.
+    pub fn prune_confirmed<R: WitnessResolver>(
+        &mut self,
+        resolver: &CachingResolver<R>,
+        min_depth: u32,
+    ) -> Result<Reclaimed, Error> {
+        let retained = self.retained_node_ids()?;
+        let mut reclaimed = Reclaimed::default();
+
+        let stale: Vec<NodeId> = self
+            .transition_iter()
+            .map(|transition| transition.node_id())
+            .filter(|node_id| !retained.contains(node_id))
+            .collect();
+
+        for node_id in stale {
+            let anchor_id = match self.indexer.anchor_id_by_transition_id(node_id)
+            {
+                Ok(anchor_id) => anchor_id,
+                Err(_) => continue,
+            };
+            let safe = self
+                .storage
+                .anchor(&anchor_id)
+                .ok()
+                .and_then(|anchor| resolver.confirmations(&anchor.txid).ok().flatten())
+                .map_or(false, |confirmations| confirmations >= min_depth);
+            if !safe {
+                continue;
+            }
+            if let Ok(transition) = self.storage.transition(&node_id) {
+                reclaimed.bytes += encoded_len(&transition);
+            }
+            if self.storage.remove_transition(&node_id).is_ok() {
+                reclaimed.nodes += 1;
+            }
+            if !self.anchor_still_needed(anchor_id, node_id, &retained) {
+                if let Ok(anchor) = self.storage.anchor(&anchor_id) {
+                    reclaimed.bytes += encoded_len(&anchor);
+                }
+                self.storage.remove_anchor(&anchor_id).ok();
+            }
+            self.indexer.remove_node(node_id)?;
+        }
+
+        // Extensions aren't anchored to a witness transaction, so there's no
+        // mined-depth to wait on: once a stale extension is unreachable from
+        // a retained tip, it's safe to reclaim immediately, same as
+        // `forget_reporting` does.
+        let stale_extensions: Vec<NodeId> = self
+            .extension_iter()
+            .map(|extension| extension.node_id())
+            .filter(|node_id| !retained.contains(node_id))
+            .collect();
+        for node_id in stale_extensions {
+            if let Ok(extension) = self.storage.extension(&node_id) {
+                reclaimed.bytes += encoded_len(&extension);
+            }
+            if self.storage.remove_extension(&node_id).is_ok() {
+                reclaimed.nodes += 1;
+            }
+            self.indexer.remove_node(node_id)?;
+        }
+
+        Ok(reclaimed)
+    }
+
+    /// Confirmation-aware variant of [`Stash::consign`]: any transition
+    /// whose witness transaction isn't mined at least `min_confirmations`
+    /// deep (or whose status can't be resolved at all, e.g. it's
+    /// `Archived`/`Tentative` or the chain tip hasn't been learned yet) is
+    /// dropped from the produced consignment, so wallets don't consign or
+    /// spend state resting on unconfirmed or orphaned witnesses. The one
+    /// exception is the consigned transition itself: if its own witness
+    /// isn't confirmed deep enough, this returns
+    /// [`Error::TipNotSufficientlyConfirmed`] instead.
+    pub fn consign_confirmed<R: WitnessResolver>(
+        &self,
+        contract_id: ContractId,
+        node: &impl Node,
+        anchor: Option<&Anchor>,
+        expose: &BTreeSet<SealEndpoint>,
+        resolver: &CachingResolver<R>,
+        min_confirmations: u32,
+    ) -> Result<Consignment, Error> {
+        let mut consignment = self.consign(contract_id, node, anchor, expose)?;
+        let tip_id = node.node_id();
+
+        let is_confirmed = |anchor: &Anchor| {
+            resolver
+                .confirmations(&anchor.txid)
+                .ok()
+                .flatten()
+                .map_or(false, |confirmations| confirmations >= min_confirmations)
+        };
+
+        // The tip is the very state the caller asked to consign: if its own
+        // witness isn't confirmed deep enough, there's nothing honest to
+        // hand back, so error instead of silently filtering it out of
+        // `state_transitions` while `endpoints` still points at it.
+        if let Some((anchor, _)) = consignment
+            .state_transitions
+            .iter()
+            .find(|(_, transition)| transition.node_id() == tip_id)
+        {
+            if !is_confirmed(anchor) {
+                return Err(Error::TipNotSufficientlyConfirmed);
+            }
+        }
+
+        consignment.state_transitions.retain(|(anchor, transition)| {
+            transition.node_id() == tip_id || is_confirmed(anchor)
+        });
+
+        Ok(consignment)
+    }
+
+    /// Registers a new contract's genesis in the stash, so it's visible to
+    /// `genesis_iter()`/`contract_ids()` (and thus to `retained_node_ids`)
+    /// even before it has any transitions merged in.
+    pub fn register_contract(&mut self, genesis: Genesis) -> Result<(), Error> {
+        let contract_id = genesis.contract_id();
+        self.storage.add_genesis(&genesis)?;
+        self.indexer.index_genesis(contract_id)?;
+        Ok(())
+    }
+
+    /// Imports an interface definition, persisting it in the stash
+    /// alongside genesis and schema data.
+    pub fn import_iface(&mut self, iface: Iface) -> Result<IfaceId, Error> {
+        let iface_id = iface.iface_id();
+        self.storage.add_iface(&iface)?;
+        Ok(iface_id)
+    }
+
+    /// Binds a schema to a previously-imported interface.
+    pub fn import_iface_impl(
+        &mut self,
+        iface_impl: IfaceImpl,
+    ) -> Result<(), Error> {
+        self.storage
+            .iface(&iface_impl.iface_id)
+            .map_err(|_| Error::UnknownIface)?;
+        self.storage.add_iface_impl(&iface_impl)?;
+        Ok(())
+    }
+
+    /// Resolves a human-readable interface name (as entered on the CLI) to
+    /// the `IfaceId` the rest of the stash indexes by.
+    pub fn iface_id_by_name(&self, name: &str) -> Result<IfaceId, Error> {
+        self.storage
+            .iface_by_name(name)
+            .map(|iface| iface.iface_id())
+            .map_err(|_| Error::UnknownIface)
+    }
+
+    /// Renders a contract's current global and owned state using the
+    /// human-readable field and assignment names of `iface_id`, instead of
+    /// raw `FieldType`/`OwnedRightType` integers.
+    ///
+    /// Global state accumulates over the contract's whole history, so every
+    /// node's metadata contributes. Owned state is only live at the tips
+    /// (nodes whose outputs haven't been spent by a later transition), so
+    /// only those contribute assignments.
+    pub fn contract_state(
+        &self,
+        contract_id: ContractId,
+        iface_id: IfaceId,
+    ) -> Result<ContractState, Error> {
+        let genesis = self.storage.genesis(&contract_id)?;
+        let iface_impl = self
+            .storage
+            .iface_impl(&genesis.schema_id(), &iface_id)
+            .map_err(|_| Error::UnknownIfaceImpl)?;
+
+        let node_ids = self.indexer.node_ids_by_contract_id(contract_id);
+
+        let mut metadata = genesis.metadata().clone();
+        let mut referenced = BTreeSet::new();
+        for node_id in &node_ids {
+            if let Ok(transition) = self.storage.transition(node_id) {
+                for (field_type, values) in transition.metadata().iter() {
+                    metadata
+                        .entry(*field_type)
+                        .or_default()
+                        .extend(values.clone());
+                }
+                referenced.extend(
+                    transition
+                        .parent_owned_rights()
+                        .into_iter()
+                        .map(|(id, _)| id),
+                );
+            } else if let Ok(extension) = self.storage.extension(node_id) {
+                for (field_type, values) in extension.metadata().iter() {
+                    metadata
+                        .entry(*field_type)
+                        .or_default()
+                        .extend(values.clone());
+                }
+                referenced.extend(
+                    extension
+                        .parent_owned_rights()
+                        .into_iter()
+                        .map(|(id, _)| id),
+                );
+            }
+        }
+
+        let mut owned_by_tip = vec![];
+        if !referenced.contains(&genesis.node_id()) {
+            owned_by_tip.push(genesis.owned_rights().clone());
+        }
+        for node_id in node_ids.iter().filter(|id| !referenced.contains(id)) {
+            if let Ok(transition) = self.storage.transition(node_id) {
+                owned_by_tip.push(transition.owned_rights().clone());
+            } else if let Ok(extension) = self.storage.extension(node_id) {
+                owned_by_tip.push(extension.owned_rights().clone());
+            }
+        }
+
+        let global = iface_impl
+            .global_fields
+            .iter()
+            .filter_map(|(name, field_type)| {
+                metadata
+                    .get(field_type)
+                    .map(|values| (name.clone(), values.clone()))
+            })
+            .collect();
+
+        let owned = iface_impl
+            .owned_rights
+            .iter()
+            .filter_map(|(name, right_type)| {
+                let assignments = owned_by_tip.iter().fold(None, |acc, rights| {
+                    match rights.get(right_type) {
+                        Some(next) => Some(merge_assignments(acc, next.clone())),
+                        None => acc,
+                    }
+                });
+                assignments.map(|assignments| (name.clone(), assignments))
+            })
+            .collect();
+
+        Ok(ContractState { global, owned })
+    }
+
+    /// Like [`Self::consign_confirmed`], but also attaches the interface and
+    /// its schema implementation to the produced consignment so the
+    /// receiver can interpret the state without out-of-band knowledge.
+    pub fn consign_with_iface<R: WitnessResolver>(
+        &self,
+        contract_id: ContractId,
+        node: &impl Node,
+        anchor: Option<&Anchor>,
+        expose: &BTreeSet<SealEndpoint>,
+        resolver: &CachingResolver<R>,
+        min_confirmations: u32,
+        iface_id: IfaceId,
+    ) -> Result<(Consignment, Iface, IfaceImpl), Error> {
+        let consignment = self.consign_confirmed(
+            contract_id,
+            node,
+            anchor,
+            expose,
+            resolver,
+            min_confirmations,
+        )?;
+        let iface = self.storage.iface(&iface_id).map_err(|_| Error::UnknownIface)?;
+        let iface_impl = self
+            .storage
+            .iface_impl(&consignment.genesis.schema_id(), &iface_id)
+            .map_err(|_| Error::UnknownIfaceImpl)?;
+        Ok((consignment, iface, iface_impl))
+    }
+}